@@ -0,0 +1,269 @@
+use anyhow::{Result, anyhow};
+use image::DynamicImage;
+use libwebp_sys::*;
+
+use crate::{PixelLayout, WebPConfig, WebPMemory, check_ok, free_picture, prepare_picture, restore_tuned_config};
+
+/// A single frame to be added to an [`AnimEncoder`].
+pub struct AnimFrame<'a> {
+    image: &'a [u8],
+    layout: PixelLayout,
+    width: u32,
+    height: u32,
+    timestamp_ms: i32,
+}
+
+impl<'a> AnimFrame<'a> {
+    /// Creates a new frame from the given image, to be shown at `timestamp_ms`.
+    pub fn from_image(image: &'a DynamicImage, timestamp_ms: i32) -> Self {
+        match image {
+            DynamicImage::ImageRgb8(image) => {
+                Self::from_rgb(image.as_ref(), image.width(), image.height(), timestamp_ms)
+            },
+            DynamicImage::ImageRgba8(image) => {
+                Self::from_rgba(image.as_ref(), image.width(), image.height(), timestamp_ms)
+            },
+            other => Self {
+                image: other.as_bytes(),
+                width: other.width(),
+                height: other.height(),
+                timestamp_ms,
+                layout: PixelLayout::Other(other.to_rgba8()),
+            },
+        }
+    }
+
+    /// Creates a new frame from raw image data in the RGB pixel layout.
+    pub fn from_rgb(image: &'a [u8], width: u32, height: u32, timestamp_ms: i32) -> Self {
+        Self {
+            image,
+            width,
+            height,
+            timestamp_ms,
+            layout: PixelLayout::RGB,
+        }
+    }
+
+    /// Creates a new frame from raw image data in the RGBA pixel layout.
+    pub fn from_rgba(image: &'a [u8], width: u32, height: u32, timestamp_ms: i32) -> Self {
+        Self {
+            image,
+            width,
+            height,
+            timestamp_ms,
+            layout: PixelLayout::RGBA,
+        }
+    }
+}
+
+/// Animation-level options, on top of the per-frame [`WebPConfig`].
+#[derive(Clone, Debug)]
+pub struct AnimEncoderOptions {
+    /// Number of times the animation should loop. `0` means infinitely.
+    pub loop_count: i32,
+    /// Background color of the canvas, stored as `0xAABBGGRR`.
+    pub background_color: u32,
+    /// If true, minimize the output size by picking the most appropriate
+    /// frame disposal / blending method, at the cost of encode time.
+    pub minimize_size: bool,
+    /// Minimum distance, in frames, between consecutive keyframes.
+    pub kmin: i32,
+    /// Maximum distance, in frames, between consecutive keyframes.
+    pub kmax: i32,
+}
+
+impl Default for AnimEncoderOptions {
+    fn default() -> Self {
+        Self {
+            loop_count: 0,
+            background_color: 0xffffffff,
+            minimize_size: true,
+            kmin: 9,
+            kmax: 17,
+        }
+    }
+}
+
+impl AnimEncoderOptions {
+    fn to_webp(&self) -> Result<WebPAnimEncoderOptions> {
+        let mut options: WebPAnimEncoderOptions = unsafe { std::mem::zeroed() };
+        let ok = unsafe { WebPAnimEncoderOptionsInitInternal(&mut options, WEBP_MUX_ABI_VERSION as _) };
+        check_ok!(ok, "anim encoder options init failed");
+
+        options.minimize_size = if self.minimize_size { 1 } else { 0 };
+        options.kmin = self.kmin;
+        options.kmax = self.kmax;
+        options.anim_params.bgcolor = self.background_color as _;
+        options.anim_params.loop_count = self.loop_count;
+
+        Ok(options)
+    }
+}
+
+/// Assembles a sequence of frames into a single animated `.webp`, wrapping
+/// libwebp's `WebPAnimEncoder` API.
+pub struct AnimEncoder<'a> {
+    frames: Vec<AnimFrame<'a>>,
+    cfg: WebPConfig,
+    options: AnimEncoderOptions,
+    width: u32,
+    height: u32,
+}
+
+impl<'a> AnimEncoder<'a> {
+    /// Creates a new animation encoder for a canvas of `width` x `height`,
+    /// using `cfg` to encode every frame.
+    pub fn new(width: u32, height: u32, cfg: WebPConfig) -> Self {
+        Self {
+            frames: Vec::new(),
+            cfg,
+            options: AnimEncoderOptions::default(),
+            width,
+            height,
+        }
+    }
+
+    /// Overrides the default animation-level options.
+    pub fn set_options(&mut self, options: AnimEncoderOptions) {
+        self.options = options;
+    }
+
+    /// Appends a frame, shown starting at its own `timestamp_ms`.
+    pub fn add_frame(&mut self, frame: AnimFrame<'a>) {
+        self.frames.push(frame);
+    }
+
+    /// Encodes the accumulated frames into an animated `.webp`.
+    ///
+    /// `duration_ms` is the timestamp, in the same timeline as each frame's
+    /// `timestamp_ms`, at which the animation ends — i.e. when the last
+    /// frame should stop being displayed. It must be greater than the last
+    /// frame's own `timestamp_ms`, or that frame would be shown for zero
+    /// duration.
+    pub fn encode(&self, duration_ms: i32) -> Result<WebPMemory> {
+        if let Some(last) = self.frames.last() {
+            if duration_ms <= last.timestamp_ms {
+                return Err(anyhow!(
+                    "duration_ms ({duration_ms}) must be greater than the last frame's timestamp_ms ({})",
+                    last.timestamp_ms
+                ));
+            }
+        }
+
+        unsafe { encode_anim(self.width, self.height, &self.cfg, &self.options, &self.frames, duration_ms) }
+    }
+}
+
+unsafe fn encode_anim(
+    width: u32,
+    height: u32,
+    cfg: &WebPConfig,
+    options: &AnimEncoderOptions,
+    frames: &[AnimFrame],
+    duration_ms: i32,
+) -> Result<WebPMemory> {
+    let webp_options = options.to_webp()?;
+    let enc = WebPAnimEncoderNewInternal(
+        width as _,
+        height as _,
+        &webp_options,
+        WEBP_MUX_ABI_VERSION as _,
+    );
+    if enc.is_null() {
+        return Err(anyhow!("failed to create WebPAnimEncoder"));
+    }
+
+    let cfg_ptr = Box::into_raw(Box::from(*cfg));
+    let ok = WebPConfigInitInternal(
+        cfg_ptr,
+        libwebp_sys::WebPPreset::WEBP_PRESET_DEFAULT,
+        cfg.quality,
+        WEBP_ENCODER_ABI_VERSION as _,
+    );
+    if ok == 0 {
+        WebPAnimEncoderDelete(enc);
+        return Err(anyhow!("config init failed"));
+    }
+    restore_tuned_config(cfg_ptr, *cfg);
+
+    for frame in frames {
+        let (image, layout) = if let PixelLayout::Other(img) = &frame.layout {
+            (img.as_ref(), &PixelLayout::RGBA)
+        } else {
+            (frame.image, &frame.layout)
+        };
+        let picture_ptr = match prepare_picture(image, layout, frame.width, frame.height, cfg.lossless) {
+            Ok(picture_ptr) => picture_ptr,
+            Err(err) => {
+                WebPAnimEncoderDelete(enc);
+                return Err(err);
+            },
+        };
+
+        let ok = WebPAnimEncoderAdd(enc, picture_ptr, frame.timestamp_ms, cfg_ptr);
+        let error_code = (*picture_ptr).error_code;
+        free_picture(picture_ptr);
+
+        if ok == 0 {
+            WebPAnimEncoderDelete(enc);
+            return Err(anyhow!("failed to add frame. libwebp error code: {:?}", error_code));
+        }
+    }
+
+    // A final NULL-frame marks the end of the animation; its timestamp is
+    // the overall duration, not the last real frame's own timestamp, or
+    // that frame's computed duration would collapse to zero.
+    let ok = WebPAnimEncoderAdd(enc, std::ptr::null_mut(), duration_ms, std::ptr::null());
+    if ok == 0 {
+        WebPAnimEncoderDelete(enc);
+        return Err(anyhow!("failed to flush animation"));
+    }
+
+    let mut webp_data: WebPData = std::mem::zeroed();
+    let ok = WebPAnimEncoderAssemble(enc, &mut webp_data);
+    WebPAnimEncoderDelete(enc);
+    if ok == 0 {
+        return Err(anyhow!("failed to assemble animation"));
+    }
+
+    Ok(WebPMemory(webp_data.bytes as *mut u8, webp_data.size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config, get_dimensions};
+
+    #[test]
+    fn test_encode_animation_produces_valid_anim_container() {
+        let news = image::open("./test_samples/news.png").expect("load image");
+        let release = image::open("./test_samples/release.png").expect("load image");
+
+        let mut encoder = AnimEncoder::new(news.width(), news.height(), config(true, 75.0, 4, true));
+        encoder.add_frame(AnimFrame::from_image(&news, 0));
+        encoder.add_frame(AnimFrame::from_image(&release, 100));
+
+        let memory = encoder.encode(200).expect("encode animation");
+
+        // A real VP8X/ANIM container, not a single-frame bitstream.
+        assert_eq!(&memory[0..4], b"RIFF");
+        assert_eq!(&memory[8..12], b"WEBP");
+        assert!(memory.windows(4).any(|chunk| chunk == b"ANIM"));
+        assert!(memory.windows(4).any(|chunk| chunk == b"ANMF"));
+
+        let dims = get_dimensions(&memory).expect("read dimensions");
+        assert_eq!(dims.width, news.width());
+        assert_eq!(dims.height, news.height());
+    }
+
+    #[test]
+    fn test_encode_rejects_duration_not_after_last_frame() {
+        let news = image::open("./test_samples/news.png").expect("load image");
+
+        let mut encoder = AnimEncoder::new(news.width(), news.height(), config(true, 75.0, 4, true));
+        encoder.add_frame(AnimFrame::from_image(&news, 100));
+
+        assert!(encoder.encode(100).is_err());
+        assert!(encoder.encode(50).is_err());
+    }
+}
@@ -0,0 +1,194 @@
+use anyhow::{Result, anyhow};
+use libwebp_sys::{WebPConfig, WebPImageHint, WebPValidateConfig};
+
+use crate::config;
+
+/// Mirrors `WebPImageHint`, the hint libwebp uses to bias its encoding
+/// choices towards a particular kind of source material.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageHint {
+    Default,
+    Photo,
+    Picture,
+    Graph,
+}
+
+impl ImageHint {
+    fn to_webp(self) -> WebPImageHint {
+        match self {
+            ImageHint::Default => WebPImageHint::WEBP_HINT_DEFAULT,
+            ImageHint::Photo => WebPImageHint::WEBP_HINT_PHOTO,
+            ImageHint::Picture => WebPImageHint::WEBP_HINT_PICTURE,
+            ImageHint::Graph => WebPImageHint::WEBP_HINT_GRAPH,
+        }
+    }
+}
+
+/// Mirrors `WebPConfig.filter_type`: simple is a faster, lower-quality
+/// deblocking filter; strong costs more time for better quality.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FilterType {
+    Simple,
+    Strong,
+}
+
+impl FilterType {
+    fn to_webp(self) -> i32 {
+        match self {
+            FilterType::Simple => 0,
+            FilterType::Strong => 1,
+        }
+    }
+}
+
+/// Builds a [`WebPConfig`] on top of [`config`]'s defaults, exposing the
+/// advanced quality-affecting knobs that `config()` otherwise hard-codes.
+/// Each setter validates its own range; [`ConfigBuilder::build`] additionally
+/// runs the result through `WebPValidateConfig` before handing it back.
+#[derive(Clone, Debug)]
+pub struct ConfigBuilder {
+    cfg: WebPConfig,
+}
+
+impl ConfigBuilder {
+    /// Starts from [`config`]'s defaults for the given base parameters.
+    pub fn new(lossless: bool, quality: f32, method: i32, multi_threading: bool) -> Self {
+        Self {
+            cfg: config(lossless, quality, method, multi_threading),
+        }
+    }
+
+    /// Number of segments to use, in `1..=4`.
+    pub fn segments(mut self, segments: i32) -> Result<Self> {
+        if !(1..=4).contains(&segments) {
+            return Err(anyhow!("segments must be within 1..=4, got {segments}"));
+        }
+        self.cfg.segments = segments;
+        Ok(self)
+    }
+
+    /// Spatial noise shaping strength, in `0..=100`.
+    pub fn sns_strength(mut self, strength: i32) -> Result<Self> {
+        if !(0..=100).contains(&strength) {
+            return Err(anyhow!("sns_strength must be within 0..=100, got {strength}"));
+        }
+        self.cfg.sns_strength = strength;
+        Ok(self)
+    }
+
+    /// Deblocking filter strength (`0..=100`), sharpness (`0..=7`), and
+    /// simple-vs-strong type. `autofilter` lets libwebp pick the strength
+    /// automatically, overriding `strength`.
+    pub fn filter(mut self, strength: i32, sharpness: i32, filter_type: FilterType, autofilter: bool) -> Result<Self> {
+        if !(0..=100).contains(&strength) {
+            return Err(anyhow!("filter strength must be within 0..=100, got {strength}"));
+        }
+        if !(0..=7).contains(&sharpness) {
+            return Err(anyhow!("filter sharpness must be within 0..=7, got {sharpness}"));
+        }
+        self.cfg.filter_strength = strength;
+        self.cfg.filter_sharpness = sharpness;
+        self.cfg.filter_type = filter_type.to_webp();
+        self.cfg.autofilter = if autofilter { 1 } else { 0 };
+        Ok(self)
+    }
+
+    /// Alpha plane compression method, filtering method, and quality
+    /// (`0..=100`).
+    pub fn alpha(mut self, compression: i32, filtering: i32, quality: i32) -> Result<Self> {
+        if !(0..=100).contains(&quality) {
+            return Err(anyhow!("alpha quality must be within 0..=100, got {quality}"));
+        }
+        self.cfg.alpha_compression = compression;
+        self.cfg.alpha_filtering = filtering;
+        self.cfg.alpha_quality = quality;
+        Ok(self)
+    }
+
+    /// Near-lossless encoding level, in `0..=100`; `100` is fully lossless.
+    pub fn near_lossless(mut self, level: i32) -> Result<Self> {
+        if !(0..=100).contains(&level) {
+            return Err(anyhow!("near_lossless must be within 0..=100, got {level}"));
+        }
+        self.cfg.near_lossless = level;
+        Ok(self)
+    }
+
+    /// Enables sharp RGB->YUV conversion, trading encode time for quality.
+    pub fn sharp_yuv(mut self, enabled: bool) -> Self {
+        self.cfg.use_sharp_yuv = if enabled { 1 } else { 0 };
+        self
+    }
+
+    /// Hints at the kind of source material being encoded.
+    pub fn image_hint(mut self, hint: ImageHint) -> Self {
+        self.cfg.image_hint = hint.to_webp();
+        self
+    }
+
+    /// Clamps the quantizer search to `qmin..=qmax` (both in `0..=100`).
+    pub fn quantizer_range(mut self, qmin: i32, qmax: i32) -> Result<Self> {
+        if !(0..=100).contains(&qmin) || !(0..=100).contains(&qmax) || qmin > qmax {
+            return Err(anyhow!("invalid quantizer range {qmin}..={qmax}"));
+        }
+        self.cfg.qmin = qmin;
+        self.cfg.qmax = qmax;
+        Ok(self)
+    }
+
+    /// Validates the accumulated config against libwebp's own rules and
+    /// returns it, ready to hand to an [`Encoder`](crate::Encoder).
+    pub fn build(self) -> Result<WebPConfig> {
+        let ok = unsafe { WebPValidateConfig(&self.cfg) };
+        if ok == 0 {
+            return Err(anyhow!("invalid WebPConfig"));
+        }
+
+        Ok(self.cfg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Decoder, Encoder};
+
+    #[test]
+    fn test_build_produces_a_usable_config() {
+        let cfg = ConfigBuilder::new(true, 80.0, 4, true)
+            .segments(2)
+            .and_then(|b| b.sns_strength(50))
+            .and_then(|b| b.filter(40, 3, FilterType::Strong, false))
+            .and_then(|b| b.alpha(1, 1, 90))
+            .and_then(|b| b.near_lossless(60))
+            .and_then(|b| b.quantizer_range(10, 90))
+            .map(|b| b.sharp_yuv(true).image_hint(ImageHint::Photo))
+            .and_then(ConfigBuilder::build)
+            .expect("build tuned config");
+
+        assert_eq!(cfg.segments, 2);
+        assert_eq!(cfg.sns_strength, 50);
+        assert_eq!(cfg.filter_strength, 40);
+        assert_eq!(cfg.filter_sharpness, 3);
+        assert_eq!(cfg.filter_type, 1);
+        assert_eq!(cfg.alpha_quality, 90);
+        assert_eq!(cfg.near_lossless, 60);
+        assert_eq!(cfg.qmin, 10);
+        assert_eq!(cfg.qmax, 90);
+
+        let image = image::open("./test_samples/news.png").expect("load image");
+        let memory = Encoder::from_image(cfg, &image).encode().expect("encode tuned config");
+        let (_, dims) = Decoder::new(&memory).decode_rgba().expect("decode tuned output");
+        assert_eq!(dims.width, image.width());
+        assert_eq!(dims.height, image.height());
+    }
+
+    #[test]
+    fn test_out_of_range_setters_are_rejected() {
+        let builder = ConfigBuilder::new(true, 80.0, 4, true);
+        assert!(builder.segments(5).is_err());
+
+        let builder = ConfigBuilder::new(true, 80.0, 4, true);
+        assert!(builder.quantizer_range(50, 10).is_err());
+    }
+}
@@ -0,0 +1,206 @@
+use anyhow::{Result, anyhow};
+use image::{DynamicImage, RgbaImage};
+use libwebp_sys::*;
+
+use crate::WebPMemory;
+
+/// Dimensions of a WebP bitstream, as reported by `WebPGetInfo` without a
+/// full decode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WebPDimensions {
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Returns the pixel dimensions of a WebP bitstream without decoding it.
+pub fn get_dimensions(data: &[u8]) -> Result<WebPDimensions> {
+    let mut width: i32 = 0;
+    let mut height: i32 = 0;
+    let ok = unsafe { WebPGetInfo(data.as_ptr(), data.len(), &mut width, &mut height) };
+    if ok == 0 {
+        return Err(anyhow!("not a valid WebP bitstream"));
+    }
+
+    Ok(WebPDimensions {
+        width: width as u32,
+        height: height as u32,
+    })
+}
+
+/// Optional scaling/cropping applied while decoding.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DecoderOptions {
+    /// Crop the input to this rectangle before scaling.
+    pub crop: Option<(i32, i32, i32, i32)>,
+    /// Rescale the (possibly cropped) output to this size.
+    pub scale: Option<(i32, i32)>,
+}
+
+/// Wraps libwebp's decode API, turning a `.webp` bitstream into raw pixels
+/// or an [`image::DynamicImage`].
+pub struct Decoder<'a> {
+    data: &'a [u8],
+    options: DecoderOptions,
+}
+
+impl<'a> Decoder<'a> {
+    /// Creates a new decoder over the given WebP bitstream.
+    pub fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            options: DecoderOptions::default(),
+        }
+    }
+
+    /// Creates a new decoder that additionally crops and/or scales its output.
+    pub fn with_options(data: &'a [u8], options: DecoderOptions) -> Self {
+        Self { data, options }
+    }
+
+    /// Decodes the bitstream into an owned RGBA buffer.
+    pub fn decode_rgba(&self) -> Result<(WebPMemory, WebPDimensions)> {
+        if self.options.crop.is_none() && self.options.scale.is_none() {
+            return self.decode_simple(true);
+        }
+
+        self.decode_advanced(MODE_RGBA)
+    }
+
+    /// Decodes the bitstream into an owned RGB buffer.
+    pub fn decode_rgb(&self) -> Result<(WebPMemory, WebPDimensions)> {
+        if self.options.crop.is_none() && self.options.scale.is_none() {
+            return self.decode_simple(false);
+        }
+
+        self.decode_advanced(MODE_RGB)
+    }
+
+    /// Decodes the bitstream into an [`image::DynamicImage`].
+    pub fn decode_image(&self) -> Result<DynamicImage> {
+        let (buffer, dims) = self.decode_rgba()?;
+        let image = RgbaImage::from_raw(dims.width, dims.height, buffer.to_vec())
+            .ok_or_else(|| anyhow!("decoded buffer does not match reported dimensions"))?;
+
+        Ok(DynamicImage::ImageRgba8(image))
+    }
+
+    fn decode_simple(&self, rgba: bool) -> Result<(WebPMemory, WebPDimensions)> {
+        let mut width: i32 = 0;
+        let mut height: i32 = 0;
+
+        let ptr = unsafe {
+            if rgba {
+                WebPDecodeRGBA(self.data.as_ptr(), self.data.len(), &mut width, &mut height)
+            } else {
+                WebPDecodeRGB(self.data.as_ptr(), self.data.len(), &mut width, &mut height)
+            }
+        };
+
+        if ptr.is_null() {
+            return Err(anyhow!("failed to decode WebP bitstream"));
+        }
+
+        let channels = if rgba { 4 } else { 3 };
+        let size = width as usize * height as usize * channels;
+        Ok((WebPMemory(ptr, size), WebPDimensions {
+            width: width as u32,
+            height: height as u32,
+        }))
+    }
+
+    fn decode_advanced(&self, mode: WEBP_CSP_MODE) -> Result<(WebPMemory, WebPDimensions)> {
+        unsafe {
+            let mut config: WebPDecoderConfig = std::mem::zeroed();
+            let ok = WebPInitDecoderConfigInternal(&mut config, WEBP_DECODER_ABI_VERSION as _);
+            if ok == 0 {
+                return Err(anyhow!("decoder config init failed"));
+            }
+
+            let status = WebPGetFeatures(self.data.as_ptr(), self.data.len(), &mut config.input);
+            if status != VP8StatusCode::VP8_STATUS_OK {
+                return Err(anyhow!("failed to parse WebP header: {:?}", status));
+            }
+
+            config.output.colorspace = mode;
+
+            if let Some((left, top, width, height)) = self.options.crop {
+                config.options.use_cropping = 1;
+                config.options.crop_left = left;
+                config.options.crop_top = top;
+                config.options.crop_width = width;
+                config.options.crop_height = height;
+            }
+            if let Some((width, height)) = self.options.scale {
+                config.options.use_scaling = 1;
+                config.options.scaled_width = width;
+                config.options.scaled_height = height;
+            }
+
+            let status = WebPDecode(self.data.as_ptr(), self.data.len(), &mut config);
+            if status != VP8StatusCode::VP8_STATUS_OK {
+                WebPFreeDecBuffer(&mut config.output);
+                return Err(anyhow!("failed to decode WebP bitstream: {:?}", status));
+            }
+
+            let buffer = &config.output.u.RGBA;
+            let width = config.output.width as u32;
+            let height = config.output.height as u32;
+            let size = buffer.size;
+            let ptr = buffer.rgba;
+
+            // The pixels now belong to us; stop `WebPFreeDecBuffer` from
+            // releasing them a second time.
+            config.output.is_external_memory = 1;
+            WebPFreeDecBuffer(&mut config.output);
+
+            Ok((WebPMemory(ptr, size), WebPDimensions { width, height }))
+        }
+    }
+}
+
+const MODE_RGBA: WEBP_CSP_MODE = WEBP_CSP_MODE::MODE_RGBA;
+const MODE_RGB: WEBP_CSP_MODE = WEBP_CSP_MODE::MODE_RGB;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Encoder, config};
+
+    #[test]
+    fn test_round_trip_lossless() {
+        let image = image::open("./test_samples/news.png").expect("load image");
+        let cfg = config(true, 100.0, 6, true);
+
+        let encoder = Encoder::from_image(cfg, &image);
+        let encoded = encoder.encode().expect("encode image");
+
+        let dims = get_dimensions(&encoded).expect("read dimensions");
+        assert_eq!(dims.width, image.width());
+        assert_eq!(dims.height, image.height());
+
+        let decoded = Decoder::new(&encoded).decode_image().expect("decode image");
+        assert_eq!(decoded.width(), image.width());
+        assert_eq!(decoded.height(), image.height());
+
+        let expected = image.to_rgba8();
+        let actual = decoded.to_rgba8();
+        assert_eq!(expected.as_raw(), actual.as_raw());
+    }
+
+    #[test]
+    fn test_round_trip_cropped() {
+        let image = image::open("./test_samples/news.png").expect("load image");
+        let cfg = config(true, 100.0, 6, true);
+
+        let encoded = Encoder::from_image(cfg, &image).encode().expect("encode image");
+
+        let options = DecoderOptions {
+            crop: Some((0, 0, 16, 16)),
+            scale: None,
+        };
+        let (_, dims) = Decoder::with_options(&encoded, options)
+            .decode_rgba()
+            .expect("decode cropped image");
+        assert_eq!(dims, WebPDimensions { width: 16, height: 16 });
+    }
+}
@@ -0,0 +1,135 @@
+use std::io::Write;
+
+use image::{ExtendedColorType, ImageEncoder, ImageResult};
+use image::error::{EncodingError, ImageError, ImageFormatHint};
+
+use crate::{Encoder, PixelLayout, config};
+
+/// The quality setting used by a [`WebPEncoder`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WebPQuality {
+    /// Lossless compression.
+    Lossless,
+    /// Lossy compression, quality in `0..=100`.
+    Lossy(f32),
+}
+
+impl WebPQuality {
+    fn to_lossless_and_quality(self) -> (bool, f32) {
+        match self {
+            WebPQuality::Lossless => (true, 100.0),
+            WebPQuality::Lossy(quality) => (false, quality.clamp(0.0, 100.0)),
+        }
+    }
+}
+
+/// Implements [`image::ImageEncoder`] on top of [`Encoder`], so `lust` can be
+/// used as a drop-in WebP backend for `image`'s `write_with_encoder`.
+pub struct WebPEncoder<W> {
+    writer: W,
+    quality: WebPQuality,
+}
+
+impl<W: Write> WebPEncoder<W> {
+    /// Creates a new encoder writing into `writer` at the given quality.
+    pub fn new(writer: W, quality: WebPQuality) -> Self {
+        Self { writer, quality }
+    }
+}
+
+impl<W: Write> ImageEncoder for WebPEncoder<W> {
+    fn write_image(
+        mut self,
+        buf: &[u8],
+        width: u32,
+        height: u32,
+        color_type: ExtendedColorType,
+    ) -> ImageResult<()> {
+        let (layout, owned);
+        let image: &[u8] = match color_type {
+            ExtendedColorType::Rgb8 => {
+                layout = PixelLayout::RGB;
+                buf
+            },
+            ExtendedColorType::Rgba8 => {
+                layout = PixelLayout::RGBA;
+                buf
+            },
+            ExtendedColorType::L8 => {
+                layout = PixelLayout::RGB;
+                owned = l8_to_rgb(buf);
+                &owned
+            },
+            ExtendedColorType::La8 => {
+                layout = PixelLayout::RGBA;
+                owned = la8_to_rgba(buf);
+                &owned
+            },
+            other => {
+                return Err(ImageError::Encoding(EncodingError::new(
+                    ImageFormatHint::Name("webp".into()),
+                    format!("unsupported color type for WebP encoding: {:?}", other),
+                )));
+            },
+        };
+
+        let (lossless, quality) = self.quality.to_lossless_and_quality();
+        let cfg = config(lossless, quality, 4, true);
+        let encoded = match layout {
+            PixelLayout::RGB => Encoder::from_rgb(cfg, image, width, height).encode(),
+            PixelLayout::RGBA => Encoder::from_rgba(cfg, image, width, height).encode(),
+            _ => unreachable!(),
+        }
+        .map_err(|err| {
+            ImageError::Encoding(EncodingError::new(ImageFormatHint::Name("webp".into()), err.to_string()))
+        })?;
+
+        self.writer.write_all(&encoded).map_err(ImageError::IoError)
+    }
+}
+
+/// Expands a grayscale buffer into RGB by repeating the luma channel.
+fn l8_to_rgb(buf: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(buf.len() * 3);
+    for &luma in buf {
+        out.extend_from_slice(&[luma, luma, luma]);
+    }
+    out
+}
+
+/// Expands a grayscale+alpha buffer into RGBA by repeating the luma channel.
+fn la8_to_rgba(buf: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(buf.len() * 2);
+    for pixel in buf.chunks_exact(2) {
+        let luma = pixel[0];
+        out.extend_from_slice(&[luma, luma, luma, pixel[1]]);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Decoder;
+
+    #[test]
+    fn test_write_with_encoder_round_trips() {
+        let image = image::open("./test_samples/news.png").expect("load image");
+        let mut buffer = Vec::new();
+
+        image
+            .write_with_encoder(WebPEncoder::new(&mut buffer, WebPQuality::Lossless))
+            .expect("write with WebPEncoder");
+
+        let decoded = Decoder::new(&buffer).decode_image().expect("decode written image");
+        assert_eq!(decoded.width(), image.width());
+        assert_eq!(decoded.height(), image.height());
+        assert_eq!(decoded.to_rgba8().as_raw(), image.to_rgba8().as_raw());
+    }
+
+    #[test]
+    fn test_lossy_quality_is_clamped() {
+        assert_eq!(WebPQuality::Lossy(150.0).to_lossless_and_quality(), (false, 100.0));
+        assert_eq!(WebPQuality::Lossy(-10.0).to_lossless_and_quality(), (false, 0.0));
+    }
+}
@@ -9,6 +9,16 @@ use libwebp_sys::WebPPreset::WEBP_PRESET_DEFAULT;
 use libwebp_sys::*;
 pub use libwebp_sys::WebPConfig;
 
+mod anim;
+mod config_builder;
+mod decode;
+mod image_encoder;
+
+pub use anim::{AnimEncoder, AnimEncoderOptions, AnimFrame};
+pub use config_builder::{ConfigBuilder, FilterType, ImageHint};
+pub use decode::{Decoder, DecoderOptions, WebPDimensions, get_dimensions};
+pub use image_encoder::{WebPEncoder, WebPQuality};
+
 
 /// Inits the global encoder config.
 ///
@@ -123,6 +133,7 @@ pub struct Encoder<'a> {
     image: &'a [u8],
     width: u32,
     height: u32,
+    progress: Option<Box<dyn FnMut(u32) -> bool + 'a>>,
 }
 
 impl<'a> Encoder<'a> {
@@ -150,6 +161,7 @@ impl<'a> Encoder<'a> {
             width,
             height,
             layout: PixelLayout::RGB,
+            progress: None,
         }
     }
 
@@ -161,6 +173,7 @@ impl<'a> Encoder<'a> {
             width,
             height,
             layout: PixelLayout::RGBA,
+            progress: None,
         }
     }
 
@@ -173,9 +186,39 @@ impl<'a> Encoder<'a> {
             width,
             height,
             layout: PixelLayout::Other(other),
+            progress: None,
         }
     }
 
+    /// Registers a progress callback, called with the completion percentage
+    /// (`0..=100`) as libwebp advances through the encode. Returning `false`
+    /// aborts the encode, surfacing [`EncodingError::Aborted`].
+    pub fn with_progress<F>(mut self, progress: F) -> Self
+    where
+        F: FnMut(u32) -> bool + 'a,
+    {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    /// Targets an output file size of at most `bytes`, letting libwebp
+    /// perform a multi-pass binary search over the quantizer to hit the
+    /// budget. `quality` still acts as a starting hint; `pass` is raised so
+    /// the search has enough iterations to converge.
+    pub fn with_target_size(mut self, bytes: i32) -> Self {
+        self.cfg.target_size = bytes;
+        self.cfg.pass = self.cfg.pass.max(8);
+        self
+    }
+
+    /// Targets a PSNR of `db` decibels instead of a fixed quality, letting
+    /// libwebp search for the quantizer that gets closest to it.
+    pub fn with_target_psnr(mut self, db: f32) -> Self {
+        self.cfg.target_PSNR = db;
+        self.cfg.pass = self.cfg.pass.max(8);
+        self
+    }
+
     /// Encode the image with the given global config.
     pub fn encode(self) -> Result<WebPMemory> {
         let (img, layout) = if let PixelLayout::Other(img) = &self.layout {
@@ -184,7 +227,35 @@ impl<'a> Encoder<'a> {
             (self.image.as_ref(), &self.layout)
         };
 
-        unsafe { encode(self.cfg, img, layout, self.width, self.height) }
+        unsafe { encode(self.cfg, img, layout, self.width, self.height, self.progress) }
+    }
+}
+
+/// Errors that carry more structure than a plain message.
+#[derive(Debug)]
+pub enum EncodingError {
+    /// The caller's progress callback returned `false`, aborting the encode.
+    Aborted,
+}
+
+impl std::fmt::Display for EncodingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EncodingError::Aborted => write!(f, "encoding was aborted by the progress callback"),
+        }
+    }
+}
+
+impl std::error::Error for EncodingError {}
+
+extern "C" fn progress_trampoline(percent: c_int, picture: *const WebPPicture) -> c_int {
+    unsafe {
+        let user_data = (*picture).user_data as *mut Box<dyn FnMut(u32) -> bool>;
+        if user_data.is_null() {
+            return 1;
+        }
+
+        if (*user_data)(percent as u32) { 1 } else { 0 }
     }
 }
 
@@ -195,44 +266,32 @@ macro_rules! check_ok {
         }
     }};
 }
+pub(crate) use check_ok;
 
-unsafe fn encode(cfg: WebPConfig, image: &[u8], layout: &PixelLayout, width: u32, height: u32) -> Result<WebPMemory> {
+/// Allocates and imports `image` into a freshly initialised [`WebPPicture`],
+/// ready to be handed to either `WebPEncode` or `WebPAnimEncoderAdd`.
+///
+/// The returned pointer is owned by the caller: it must eventually be passed
+/// to [`free_picture`] once libwebp is done with it.
+pub(crate) unsafe fn prepare_picture(
+    image: &[u8],
+    layout: &PixelLayout,
+    width: u32,
+    height: u32,
+    use_argb: c_int,
+) -> Result<*mut WebPPicture> {
     let picture = empty_webp_picture();
-    let writer = WebPMemoryWriter {
-        mem: std::ptr::null_mut::<u8>(),
-        size: 0,
-        max_size: 0,
-        pad: [0],
-    };
-
-    let cfg_ptr = Box::into_raw(Box::from(cfg));
     let picture_ptr = Box::into_raw(Box::from(picture));
-    let writer_ptr = Box::into_raw(Box::from(writer));
-
-    let ok = WebPConfigInitInternal(
-        cfg_ptr,
-        WEBP_PRESET_DEFAULT,
-        cfg.quality,
-        WEBP_ENCODER_ABI_VERSION as c_int,
-    );
-    check_ok!(ok, "config init failed");
 
     let ok = WebPPictureInitInternal(picture_ptr, WEBP_ENCODER_ABI_VERSION as c_int);
     check_ok!(ok, "picture init failed");
 
-    (*picture_ptr).use_argb = cfg.lossless;
-    (*cfg_ptr).lossless = cfg.lossless;
-    (*cfg_ptr).method = cfg.method;
-    (*cfg_ptr).thread_level = cfg.thread_level;
+    (*picture_ptr).use_argb = use_argb;
 
     let width = width as _;
     let height = height as _;
-
     (*picture_ptr).width = width;
     (*picture_ptr).height = height;
-    (*picture_ptr).writer = WebPWriterFunction::Some(WebPMemoryWrite);
-    (*picture_ptr).custom_ptr = writer_ptr as *mut _;
-    WebPMemoryWriterInit(writer_ptr);
 
     let ok = match layout {
         PixelLayout::RGB => {
@@ -255,14 +314,78 @@ unsafe fn encode(cfg: WebPConfig, image: &[u8], layout: &PixelLayout, width: u32
     };
     check_ok!(ok, "failed to import image");
 
-    let ok = WebPEncode(cfg_ptr, picture_ptr);
+    Ok(picture_ptr)
+}
+
+/// Releases a `WebPPicture` produced by [`prepare_picture`]: both libwebp's
+/// own internal buffers (via `WebPPictureFree`) and the `Box` the picture
+/// itself was allocated into.
+pub(crate) unsafe fn free_picture(picture_ptr: *mut WebPPicture) {
     WebPPictureFree(picture_ptr);
+    drop(Box::from_raw(picture_ptr));
+}
+
+/// Restores `cfg` onto `cfg_ptr` wholesale.
+///
+/// `WebPConfigInitInternal` is only called to validate the ABI version, but
+/// as a side effect it also clobbers every field back to its own preset
+/// defaults. `cfg` is already fully populated (by [`config`] or
+/// [`ConfigBuilder`](crate::ConfigBuilder)), so the whole struct is restored
+/// here rather than re-threading individual fields by hand at each call site.
+pub(crate) unsafe fn restore_tuned_config(cfg_ptr: *mut WebPConfig, cfg: WebPConfig) {
+    *cfg_ptr = cfg;
+}
+
+unsafe fn encode(
+    cfg: WebPConfig,
+    image: &[u8],
+    layout: &PixelLayout,
+    width: u32,
+    height: u32,
+    mut progress: Option<Box<dyn FnMut(u32) -> bool + '_>>,
+) -> Result<WebPMemory> {
+    let writer = WebPMemoryWriter {
+        mem: std::ptr::null_mut::<u8>(),
+        size: 0,
+        max_size: 0,
+        pad: [0],
+    };
+
+    let cfg_ptr = Box::into_raw(Box::from(cfg));
+    let writer_ptr = Box::into_raw(Box::from(writer));
+
+    let ok = WebPConfigInitInternal(
+        cfg_ptr,
+        WEBP_PRESET_DEFAULT,
+        cfg.quality,
+        WEBP_ENCODER_ABI_VERSION as c_int,
+    );
+    check_ok!(ok, "config init failed");
+
+    restore_tuned_config(cfg_ptr, cfg);
+
+    let picture_ptr = prepare_picture(image, layout, width, height, cfg.lossless)?;
+
+    (*picture_ptr).writer = WebPWriterFunction::Some(WebPMemoryWrite);
+    (*picture_ptr).custom_ptr = writer_ptr as *mut _;
+    WebPMemoryWriterInit(writer_ptr);
+
+    // `user_data` borrows `progress` for the duration of `WebPEncode`; the
+    // trampoline recovers it by pointer, never taking ownership.
+    if let Some(progress) = &mut progress {
+        (*picture_ptr).user_data = progress as *mut Box<dyn FnMut(u32) -> bool + '_> as *mut _;
+        (*picture_ptr).progress_hook = Some(progress_trampoline);
+    }
+
+    let ok = WebPEncode(cfg_ptr, picture_ptr);
+    let error_code = (*picture_ptr).error_code;
+    free_picture(picture_ptr);
     if ok == 0 {
         WebPMemoryWriterClear(writer_ptr);
-        return Err(anyhow!(
-            "memory error. libwebp error code: {:?}",
-            (*picture_ptr).error_code
-        ))
+        if error_code == WebPEncodingError::VP8_ENC_ERROR_USER_ABORT {
+            return Err(EncodingError::Aborted.into());
+        }
+        return Err(anyhow!("memory error. libwebp error code: {:?}", error_code));
     }
 
     Ok(WebPMemory((*writer_ptr).mem, (*writer_ptr).size))
@@ -334,4 +457,62 @@ mod tests {
 
         write("./release.webp", buffer).expect("write image");
     }
+
+    #[test]
+    fn test_target_size_produces_smaller_output() {
+        let image = image::open("./test_samples/news.png").expect("load image");
+
+        let baseline = Encoder::from_image(config(false, 100.0, 4, true), &image)
+            .encode()
+            .expect("encode baseline");
+
+        let budget = baseline.len() / 2;
+        let budgeted = Encoder::from_image(config(false, 100.0, 4, true), &image)
+            .with_target_size(budget as i32)
+            .encode()
+            .expect("encode with target size");
+
+        assert!(budgeted.len() < baseline.len());
+    }
+
+    #[test]
+    fn test_target_psnr_encodes_successfully() {
+        let image = image::open("./test_samples/news.png").expect("load image");
+
+        let memory = Encoder::from_image(config(false, 75.0, 4, true), &image)
+            .with_target_psnr(40.0)
+            .encode()
+            .expect("encode with target PSNR");
+
+        assert!(!memory.is_empty());
+    }
+
+    #[test]
+    fn test_progress_callback_runs_and_succeeds() {
+        let image = image::open("./test_samples/news.png").expect("load image");
+        let mut calls = 0u32;
+
+        let memory = Encoder::from_image(config(false, 75.0, 4, true), &image)
+            .with_progress(|percent| {
+                calls += 1;
+                percent <= 100
+            })
+            .encode()
+            .expect("encode with progress callback");
+
+        assert!(calls > 0);
+        assert!(!memory.is_empty());
+    }
+
+    #[test]
+    fn test_progress_callback_abort_surfaces_aborted_error() {
+        let image = image::open("./test_samples/news.png").expect("load image");
+
+        let err = Encoder::from_image(config(false, 75.0, 4, true), &image)
+            .with_progress(|_percent| false)
+            .encode()
+            .expect_err("callback returning false should abort the encode");
+
+        assert!(err.downcast_ref::<EncodingError>().is_some());
+    }
 }